@@ -60,13 +60,23 @@ mod benchmarking;
 // pub mod weights;
 // pub use weights::*;
 
+/// The `sp_api::decl_runtime_apis!`-defined API a node's runtime implements so RPCs can query
+/// the order book without decoding raw storage.
+pub mod runtime_api;
+
+/// The `jsonrpsee` RPC server a node wires in to expose [`runtime_api::AuctionApi`] to clients.
+#[cfg(feature = "std")]
+pub mod rpc;
+
 #[frame_support::pallet]
 pub mod pallet {
 
     use super::*;
     use frame_support::inherent::Vec;
-    use frame_support::{pallet_prelude::*, Twox64Concat};
+    use frame_support::traits::{BalanceStatus, ReservableCurrency};
+    use frame_support::{pallet_prelude::*, Blake2_128Concat, Twox64Concat};
     use frame_system::pallet_prelude::*;
+    use sp_runtime::traits::{Hash, SaturatedConversion, Zero};
 
     #[pallet::pallet]
     #[pallet::without_storage_info]
@@ -77,6 +87,13 @@ pub mod pallet {
     pub trait Config: frame_system::Config {
         /// Because this pallet emits events, it depends on the runtime's definition of an event.
         type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+        /// The currency used to escrow buyer deposits and settle seller payments.
+        type Currency: ReservableCurrency<Self::AccountId, Balance = u128>;
+
+        /// Ascending quantity boundaries used to sort a sale into a [`Tier`]; see
+        /// [`Pallet::tier_for_quantity`].
+        type TierThresholds: Get<Vec<u128>>;
         // /// Type representing the weight of this pallet
         // type WeightInfo: WeightInfo;
     }
@@ -85,17 +102,20 @@ pub mod pallet {
     // Storage types   //
     /////////////////////
 
-    // Buyers bid
+    // Buyers bid: `bid` is a price per unit of energy, `quantity` how much the buyer wants at
+    // that price or better.
     #[derive(Clone, Encode, Decode, Default, Eq, PartialEq, RuntimeDebug, TypeInfo)]
     pub struct Bid<AccountId> {
-        buyer_id: AccountId,
-        bid: u128,
+        pub buyer_id: AccountId,
+        pub bid: u128,
+        pub quantity: u128,
     }
 
     // Status of an auction, live auctions accepts bids
     #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
     pub enum AuctionStatus {
         Alive,
+        Matched,
         Dead,
     }
     impl Default for AuctionStatus {
@@ -110,13 +130,33 @@ pub mod pallet {
         pub seller_id: AccountId,
         pub quantity: u128,
         pub starting_bid: u128,
-        bids: Vec<Bid>,
-        auction_period: BlockNumber,
-        auction_status: AuctionStatus,
-        start_at: BlockNumber,
-        ended_at: BlockNumber,
-        highest_bid: Bid,
-        auction_category: Tier,
+        pub bids: Vec<Bid>,
+        pub auction_period: BlockNumber,
+        pub auction_status: AuctionStatus,
+        pub start_at: BlockNumber,
+        pub ended_at: BlockNumber,
+        pub highest_bid: Bid,
+        pub auction_category: Tier,
+        pub auction_kind: AuctionKind,
+        // Only meaningful when `auction_kind` is `Dutch`.
+        pub start_price: u128,
+        pub floor_price: u128,
+    }
+
+    // The mechanism used to match a seller's auction against buyers.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    pub enum AuctionKind {
+        // The order book: bids accumulate until `ended_at`, then the top bids clear at a
+        // single uniform price, the last one possibly partially.
+        OrderBook,
+        // A declining-price auction: the ask falls linearly from `start_price` to `floor_price`
+        // over `auction_period`, and the first bid that meets the current ask wins instantly.
+        Dutch,
+    }
+    impl Default for AuctionKind {
+        fn default() -> Self {
+            AuctionKind::OrderBook
+        }
     }
 
     // Tier of an auction sale
@@ -131,18 +171,31 @@ pub mod pallet {
         }
     }
 
+    pub(super) type AuctionOf<T> = AuctionData<
+        <T as frame_system::Config>::AccountId,
+        <T as frame_system::Config>::BlockNumber,
+        Bid<<T as frame_system::Config>::AccountId>,
+        Tier,
+    >;
+
     //////////////////////
     // Storage item    //
     /////////////////////
     #[pallet::storage]
     #[pallet::getter(fn get_auction)]
-    pub(super) type Auctions<T: Config> = StorageMap<
-        _,
-        Twox64Concat,
-        T::Hash,
-        AuctionData<T::AccountId, T::BlockNumber, Bid<T::AccountId>, Tier>,
-        OptionQuery,
-    >;
+    pub(super) type Auctions<T: Config> = StorageMap<_, Twox64Concat, T::Hash, AuctionOf<T>, OptionQuery>;
+
+    // Index of auction ids due to expire at a given block, so `on_initialize` only has to
+    // look at auctions that actually end this block instead of scanning all of `Auctions`.
+    #[pallet::storage]
+    pub(super) type ExpiringAuctions<T: Config> =
+        StorageMap<_, Twox64Concat, T::BlockNumber, Vec<T::Hash>, ValueQuery>;
+
+    // Secondary index of live auctions by tier, so the `auctions_by_tier` runtime API can
+    // answer without scanning all of `Auctions`.
+    #[pallet::storage]
+    pub(super) type AuctionsByTier<T: Config> =
+        StorageDoubleMap<_, Blake2_128Concat, u32, Twox64Concat, T::Hash, (), ValueQuery>;
 
     //////////////////////
     // Runtime events  //
@@ -161,6 +214,7 @@ pub mod pallet {
             seller: T::AccountId,
             buyer: T::AccountId,
             energy_quantity: u128,
+            filled_quantity: u128,
             starting_price: u128,
             highest_bid: u128,
             matched_at: T::BlockNumber,
@@ -170,6 +224,7 @@ pub mod pallet {
             seller: T::AccountId,
             buyer: T::AccountId,
             energy_quantity: u128,
+            filled_quantity: u128,
             starting_price: u128,
             highest_bid: u128,
             executed_at: T::BlockNumber,
@@ -180,6 +235,22 @@ pub mod pallet {
             energy_quantity: u128,
             starting_price: u128,
         },
+
+        BidPlaced {
+            seller: T::AccountId,
+            buyer: T::AccountId,
+            bid: u128,
+        },
+
+        // Emitted instead of `AuctionExecuted` when a cleared bid's escrow could not be fully
+        // repatriated to the seller (e.g. an existential-deposit dust check on their account).
+        AuctionSettlementFailed {
+            seller: T::AccountId,
+            buyer: T::AccountId,
+            filled_quantity: u128,
+            shortfall: u128,
+            attempted_at: T::BlockNumber,
+        },
     }
 
     //////////////////////
@@ -195,6 +266,227 @@ pub mod pallet {
         UnAuthorizedCall,
 
         InsuffficientAttachedDeposit,
+
+        BidTooLow,
+
+        // A cleared bid's escrowed deposit could not be fully repatriated to the seller.
+        SettlementTransferFailed,
+
+        // A Dutch auction bid's `quantity` didn't match the seller's whole lot.
+        QuantityMustMatchLot,
+
+        // A buyer already has a bid on this auction.
+        DuplicateBid,
+
+        // The bid's quantity sorts it into a different tier than the auction it targets.
+        TierMismatch,
+
+        // `on_initialize` runs before extrinsics in a block, so an auction whose `ended_at`
+        // lands on the block it was created in would be indexed into a block `ExpiringAuctions`
+        // entry the sweep already passed, and so never get auto-settled.
+        ZeroAuctionPeriod,
+    }
+
+    //////////////////////
+    // Pallet hooks    //
+    /////////////////////
+    #[pallet::hooks]
+    impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {
+        fn on_initialize(now: T::BlockNumber) -> Weight {
+            let due = <ExpiringAuctions<T>>::take(now);
+            let mut weight = T::DbWeight::get().reads(1);
+
+            for auction_id in due {
+                weight = weight.saturating_add(Self::settle_auction(auction_id, now));
+            }
+
+            weight
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        // Clear an order-book auction once its period is over and pay out the fills.
+        // No-op (besides the read) if the auction is gone or no longer `Alive`; an auction with
+        // no bids, or none that clear, is just marked `Dead`.
+        fn settle_auction(auction_id: T::Hash, now: T::BlockNumber) -> Weight {
+            let mut weight = T::DbWeight::get().reads(1);
+
+            <Auctions<T>>::mutate(auction_id, |maybe_auction| {
+                let auction = match maybe_auction.as_mut() {
+                    Some(auction) => auction,
+                    None => return,
+                };
+
+                if auction.auction_status != AuctionStatus::Alive {
+                    return;
+                }
+
+                // This auction is leaving `Alive` one way or another; it drops out of the
+                // by-tier index of live auctions either way.
+                <AuctionsByTier<T>>::remove(auction.auction_category.level, auction_id);
+
+                if auction.bids.is_empty() {
+                    auction.auction_status = AuctionStatus::Dead;
+                    return;
+                }
+
+                let (fills, clearing_price) = Self::clear_bids(&auction.bids, auction.quantity);
+
+                // Everyone who didn't clear (price too low, or the losing half of a pro-rata
+                // tie) gets their whole deposit back.
+                let filled_buyers: Vec<_> = fills.iter().map(|(bid, _)| bid.buyer_id.clone()).collect();
+                for bid in auction.bids.iter() {
+                    if !filled_buyers.contains(&bid.buyer_id) {
+                        T::Currency::unreserve(&bid.buyer_id, bid.bid.saturating_mul(bid.quantity));
+                    }
+                }
+
+                if fills.is_empty() {
+                    auction.auction_status = AuctionStatus::Dead;
+                    return;
+                }
+
+                for (bid, filled_quantity) in fills {
+                    // Every cleared bid pays the single uniform `clearing_price`, never its own
+                    // (possibly higher) bid; refund the difference on top of what it reserved
+                    // for quantity it didn't get filled.
+                    let reserved = bid.bid.saturating_mul(bid.quantity);
+                    let cost = clearing_price.saturating_mul(filled_quantity);
+                    let refund = reserved.saturating_sub(cost);
+                    if refund > 0 {
+                        T::Currency::unreserve(&bid.buyer_id, refund);
+                    }
+
+                    weight = weight.saturating_add(T::DbWeight::get().writes(1));
+                    // `on_initialize` has no error path to bail to; a transfer failure here is
+                    // already surfaced as `Event::AuctionSettlementFailed` by `execute_match`
+                    // itself, and the buyer's cost stays reserved rather than vanishing.
+                    let _ = Self::execute_match(auction, bid.buyer_id, clearing_price, filled_quantity, now);
+                }
+            });
+
+            weight
+        }
+
+        // Uniform-price clearing over the descending-sorted `bids`, returning the (bid, filled
+        // quantity) pairs that clear along with the single clearing price. Ties at the clearing
+        // price are split pro-rata by quantity.
+        fn clear_bids(bids: &[Bid<T::AccountId>], quantity: u128) -> (Vec<(Bid<T::AccountId>, u128)>, u128) {
+            let mut fills = Vec::new();
+            let mut clearing_price = 0u128;
+            let mut remaining = quantity;
+            let mut i = 0;
+
+            while remaining > 0 && i < bids.len() {
+                let price = bids[i].bid;
+                let mut j = i;
+                let mut group_quantity = 0u128;
+                while j < bids.len() && bids[j].bid == price {
+                    group_quantity = group_quantity.saturating_add(bids[j].quantity);
+                    j += 1;
+                }
+
+                clearing_price = price;
+                if group_quantity <= remaining {
+                    for bid in &bids[i..j] {
+                        fills.push((bid.clone(), bid.quantity));
+                    }
+                    remaining -= group_quantity;
+                } else {
+                    // Tie at the clearing price: split what's left pro-rata by quantity.
+                    for bid in &bids[i..j] {
+                        let allocation = bid.quantity.saturating_mul(remaining) / group_quantity;
+                        if allocation > 0 {
+                            fills.push((bid.clone(), allocation));
+                        }
+                    }
+                    remaining = 0;
+                }
+
+                i = j;
+            }
+
+            (fills, clearing_price)
+        }
+
+        // The current Dutch-auction ask for `auction` at block `now`, clamped to `floor_price`.
+        // Meaningless for `AuctionKind::OrderBook` auctions.
+        fn dutch_price_at(auction: &AuctionOf<T>, now: T::BlockNumber) -> u128 {
+            if now <= auction.start_at || auction.start_price <= auction.floor_price {
+                return auction.start_price.max(auction.floor_price);
+            }
+
+            let elapsed = now.saturating_sub(auction.start_at);
+            if elapsed >= auction.auction_period {
+                return auction.floor_price;
+            }
+
+            let elapsed: u128 = elapsed.saturated_into();
+            let period: u128 = auction.auction_period.saturated_into();
+            let decay = (auction.start_price - auction.floor_price).saturating_mul(elapsed) / period;
+
+            auction.start_price.saturating_sub(decay).max(auction.floor_price)
+        }
+
+        // Settle a single filled bid: flip the auction's status, move exactly `price *
+        // filled_quantity` of the buyer's escrowed deposit to the seller, and emit the
+        // AuctionMatched/AuctionExecuted events. Shared by the order-book clearing pass and
+        // instant Dutch-auction matches.
+        //
+        // If the repatriation doesn't fully land (the seller's account rejects the transfer,
+        // e.g. an existential-deposit dust check), this does NOT emit `AuctionExecuted` and
+        // instead returns an error: payment was not actually made, so callers must not treat
+        // the match as settled.
+        fn execute_match(
+            auction: &mut AuctionOf<T>,
+            buyer: T::AccountId,
+            price: u128,
+            filled_quantity: u128,
+            now: T::BlockNumber,
+        ) -> DispatchResult {
+            auction.auction_status = AuctionStatus::Matched;
+
+            Self::deposit_event(Event::AuctionMatched {
+                seller: auction.seller_id.clone(),
+                buyer: buyer.clone(),
+                energy_quantity: auction.quantity,
+                filled_quantity,
+                starting_price: auction.starting_bid,
+                highest_bid: price,
+                matched_at: now,
+            });
+
+            let cost = price.saturating_mul(filled_quantity);
+            let shortfall =
+                match T::Currency::repatriate_reserved(&buyer, &auction.seller_id, cost, BalanceStatus::Free) {
+                    Ok(shortfall) => shortfall,
+                    // Nothing was moved at all: the whole cost is the shortfall.
+                    Err(_) => cost,
+                };
+
+            if shortfall > 0 {
+                Self::deposit_event(Event::AuctionSettlementFailed {
+                    seller: auction.seller_id.clone(),
+                    buyer,
+                    filled_quantity,
+                    shortfall,
+                    attempted_at: now,
+                });
+                return Err(Error::<T>::SettlementTransferFailed.into());
+            }
+
+            Self::deposit_event(Event::AuctionExecuted {
+                seller: auction.seller_id.clone(),
+                buyer,
+                energy_quantity: auction.quantity,
+                filled_quantity,
+                starting_price: auction.starting_bid,
+                highest_bid: price,
+                executed_at: now,
+            });
+
+            Ok(())
+        }
     }
 
     ///////////////////////////
@@ -202,10 +494,229 @@ pub mod pallet {
     //////////////////////////
     #[pallet::call]
     impl<T: Config> Pallet<T> {
+        /// Open a new auction for `quantity` units of energy, starting at `starting_bid`
+        /// and accepting bids for `auction_period` blocks.
+        ///
+        /// `start_price`/`floor_price` only matter when `auction_kind` is `Dutch`; pass `0` for
+        /// both on an `OrderBook` auction.
+        ///
+        /// `salt` is folded into the `AuctionData` hash so a seller can have more than one
+        /// auction with otherwise identical parameters live at the same time.
         #[pallet::call_index(0)]
         #[pallet::weight(100_000_000)]
-        pub fn create_auction(_origin: OriginFor<T>) -> DispatchResult {
+        pub fn create_auction(
+            origin: OriginFor<T>,
+            quantity: u128,
+            starting_bid: u128,
+            auction_period: T::BlockNumber,
+            salt: u128,
+            auction_kind: AuctionKind,
+            start_price: u128,
+            floor_price: u128,
+        ) -> DispatchResult {
+            let seller = ensure_signed(origin)?;
+
+            ensure!(!auction_period.is_zero(), Error::<T>::ZeroAuctionPeriod);
+
+            let start_at = <frame_system::Pallet<T>>::block_number();
+            let ended_at = start_at + auction_period;
+
+            let auction = AuctionData {
+                seller_id: seller.clone(),
+                quantity,
+                starting_bid,
+                bids: Vec::new(),
+                auction_period,
+                auction_status: AuctionStatus::Alive,
+                start_at,
+                ended_at,
+                highest_bid: Bid::default(),
+                auction_category: Self::tier_for_quantity(quantity),
+                auction_kind,
+                start_price,
+                floor_price,
+            };
+
+            let auction_id = T::Hashing::hash_of(&(&auction, salt));
+            <AuctionsByTier<T>>::insert(auction.auction_category.level, auction_id, ());
+            <Auctions<T>>::insert(auction_id, auction);
+            <ExpiringAuctions<T>>::mutate(ended_at, |due| due.push(auction_id));
+
+            Self::deposit_event(Event::AuctionCreated {
+                seller,
+                energy_quantity: quantity,
+                starting_price: starting_bid,
+            });
+
             Ok(())
         }
+
+        /// Place a `bid` on the auction identified by `auction_id`.
+        ///
+        /// Bids are kept sorted in descending order by price so clearing can walk them from the
+        /// top down; the full `bid * quantity` cost is reserved up front and refunded down to
+        /// whatever is actually filled once the auction clears.
+        #[pallet::call_index(1)]
+        #[pallet::weight(100_000_000)]
+        pub fn bid_on_auction(
+            origin: OriginFor<T>,
+            auction_id: T::Hash,
+            bid: u128,
+            quantity: u128,
+        ) -> DispatchResult {
+            let buyer = ensure_signed(origin)?;
+
+            <Auctions<T>>::try_mutate(auction_id, |maybe_auction| -> DispatchResult {
+                let auction = maybe_auction
+                    .as_mut()
+                    .ok_or(Error::<T>::AuctionDoesNotExist)?;
+
+                let now = <frame_system::Pallet<T>>::block_number();
+                ensure!(
+                    auction.auction_status == AuctionStatus::Alive && now <= auction.ended_at,
+                    Error::<T>::AuctionIsOver
+                );
+
+                // A buyer is categorized by how much they intend to buy, the same way a seller
+                // is categorized by how much they're selling; only bids in the auction's own
+                // tier may match it.
+                ensure!(
+                    Self::tier_for_quantity(quantity).level == auction.auction_category.level,
+                    Error::<T>::TierMismatch
+                );
+
+                if auction.auction_kind == AuctionKind::Dutch {
+                    // Dutch auctions are all-or-nothing: the first bid that meets the ask takes
+                    // the whole lot, so a caller-supplied `quantity` that doesn't match would
+                    // otherwise be silently ignored.
+                    ensure!(quantity == auction.quantity, Error::<T>::QuantityMustMatchLot);
+
+                    let ask = Self::dutch_price_at(auction, now);
+                    ensure!(bid >= ask, Error::<T>::BidTooLow);
+
+                    T::Currency::reserve(&buyer, ask.saturating_mul(auction.quantity))
+                        .map_err(|_| Error::<T>::InsuffficientAttachedDeposit)?;
+                    <AuctionsByTier<T>>::remove(auction.auction_category.level, auction_id);
+                    // Propagate a settlement failure so the whole extrinsic is rolled back,
+                    // including the `reserve` above, rather than charging the buyer for a
+                    // match that was never actually paid out to the seller.
+                    Self::execute_match(auction, buyer.clone(), ask, auction.quantity, now)?;
+
+                    Self::deposit_event(Event::BidPlaced {
+                        seller: auction.seller_id.clone(),
+                        buyer,
+                        bid: ask,
+                    });
+
+                    return Ok(());
+                }
+
+                // One bid per buyer per auction: `settle_auction` tells filled bids apart from
+                // unfilled ones by `buyer_id` alone, so a second bid from the same buyer would
+                // make an unfilled bid indistinguishable from a filled one and strand its
+                // reservation.
+                ensure!(
+                    auction.bids.iter().all(|existing| existing.buyer_id != buyer),
+                    Error::<T>::DuplicateBid
+                );
+
+                T::Currency::reserve(&buyer, bid.saturating_mul(quantity))
+                    .map_err(|_| Error::<T>::InsuffficientAttachedDeposit)?;
+
+                let position = auction
+                    .bids
+                    .iter()
+                    .position(|existing| existing.bid < bid)
+                    .unwrap_or(auction.bids.len());
+                auction.bids.insert(
+                    position,
+                    Bid {
+                        buyer_id: buyer.clone(),
+                        bid,
+                        quantity,
+                    },
+                );
+                auction.highest_bid = auction.bids[0].clone();
+
+                Self::deposit_event(Event::BidPlaced {
+                    seller: auction.seller_id.clone(),
+                    buyer,
+                    bid,
+                });
+
+                Ok(())
+            })
+        }
+
+        /// Withdraw an auction before it is matched. Only the seller who created it may do so.
+        #[pallet::call_index(2)]
+        #[pallet::weight(100_000_000)]
+        pub fn destroy_auction(origin: OriginFor<T>, auction_id: T::Hash) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            <Auctions<T>>::try_mutate(auction_id, |maybe_auction| -> DispatchResult {
+                let auction = maybe_auction
+                    .as_mut()
+                    .ok_or(Error::<T>::AuctionDoesNotExist)?;
+
+                ensure!(auction.seller_id == who, Error::<T>::UnAuthorizedCall);
+                ensure!(auction.auction_status == AuctionStatus::Alive, Error::<T>::AuctionIsOver);
+
+                for bid in auction.bids.iter() {
+                    T::Currency::unreserve(&bid.buyer_id, bid.bid.saturating_mul(bid.quantity));
+                }
+                <AuctionsByTier<T>>::remove(auction.auction_category.level, auction_id);
+                auction.auction_status = AuctionStatus::Dead;
+
+                Self::deposit_event(Event::AuctionDestroyed {
+                    seller: auction.seller_id.clone(),
+                    energy_quantity: auction.quantity,
+                    starting_price: auction.starting_bid,
+                });
+
+                Ok(())
+            })
+        }
+    }
+
+    // Read-only helpers backing `runtime_api::AuctionApi`; a node's runtime calls these from
+    // its `impl_runtime_apis!` block.
+    impl<T: Config> Pallet<T> {
+        /// The auctions in the given tier that are still `Alive`.
+        pub fn auctions_by_tier(level: u32) -> Vec<(T::Hash, AuctionOf<T>)> {
+            <AuctionsByTier<T>>::iter_prefix(level)
+                .filter_map(|(auction_id, ())| {
+                    Self::get_auction(auction_id).map(|auction| (auction_id, auction))
+                })
+                .collect()
+        }
+
+        /// The current highest bid in an auction's order book, if it has received one. A Dutch
+        /// auction's winning bid is not kept in the order book, so this is `None` for it.
+        pub fn best_bid(auction_id: T::Hash) -> Option<Bid<T::AccountId>> {
+            let auction = Self::get_auction(auction_id)?;
+            auction.bids.first().cloned()
+        }
+
+        /// The ids of every auction that is still `Alive` and accepting bids.
+        pub fn live_auctions() -> Vec<T::Hash> {
+            <Auctions<T>>::iter()
+                .filter(|(_, auction)| auction.auction_status == AuctionStatus::Alive)
+                .map(|(auction_id, _)| auction_id)
+                .collect()
+        }
+
+        /// Classify a sale into a [`Tier`] from `Config::TierThresholds`: `level` is `1` plus
+        /// the number of ascending boundaries `quantity` meets or exceeds, so a higher quantity
+        /// of energy for sale leads to a higher tier.
+        pub fn tier_for_quantity(quantity: u128) -> Tier {
+            let level = T::TierThresholds::get()
+                .iter()
+                .filter(|&&threshold| quantity >= threshold)
+                .count() as u32
+                + 1;
+
+            Tier { level }
+        }
     }
 }
@@ -0,0 +1,101 @@
+//! `jsonrpsee` server exposing [`crate::runtime_api::AuctionApi`] to off-chain clients.
+//!
+//! A node wires this in alongside its other RPC extensions, e.g.:
+//!
+//! ```ignore
+//! io.merge(Auction::new(client.clone()).into_rpc())?;
+//! ```
+
+use std::sync::Arc;
+
+use codec::Codec;
+use jsonrpsee::{
+    core::RpcResult,
+    proc_macros::rpc,
+    types::error::{ErrorObject, ErrorObjectOwned},
+};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::Block as BlockT;
+
+use crate::runtime_api::AuctionApi as AuctionRuntimeApi;
+use crate::{AuctionData, Bid, Tier};
+
+/// RPC methods for reading the auction order book.
+#[rpc(client, server)]
+pub trait AuctionApi<BlockHash, AccountId, BlockNumber, Hash> {
+    /// The auctions in the given tier that are still `Alive`.
+    #[method(name = "auction_auctionsByTier")]
+    fn auctions_by_tier(
+        &self,
+        level: u32,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Vec<(Hash, AuctionData<AccountId, BlockNumber, Bid<AccountId>, Tier>)>>;
+
+    /// The current highest bid in an auction's order book, if it has received one. A Dutch
+    /// auction's winning bid is not kept in the order book, so this is `None` for it.
+    #[method(name = "auction_bestBid")]
+    fn best_bid(&self, auction_id: Hash, at: Option<BlockHash>) -> RpcResult<Option<Bid<AccountId>>>;
+
+    /// The ids of every auction that is still `Alive` and accepting bids.
+    #[method(name = "auction_liveAuctions")]
+    fn live_auctions(&self, at: Option<BlockHash>) -> RpcResult<Vec<Hash>>;
+}
+
+/// The RPC server, backed by a client able to call into [`crate::runtime_api::AuctionApi`].
+pub struct Auction<C, Block> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<Block>,
+}
+
+impl<C, Block> Auction<C, Block> {
+    pub fn new(client: Arc<C>) -> Self {
+        Self {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+fn runtime_error(what: &str, err: impl std::fmt::Debug) -> ErrorObjectOwned {
+    ErrorObject::owned(1, format!("{what}: {err:?}"), None::<()>)
+}
+
+impl<C, Block, AccountId, BlockNumber, Hash> AuctionApiServer<Block::Hash, AccountId, BlockNumber, Hash>
+    for Auction<C, Block>
+where
+    Block: BlockT,
+    AccountId: Codec,
+    BlockNumber: Codec,
+    Hash: Codec,
+    C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+    C::Api: AuctionRuntimeApi<Block, AccountId, BlockNumber, Hash>,
+{
+    fn auctions_by_tier(
+        &self,
+        level: u32,
+        at: Option<Block::Hash>,
+    ) -> RpcResult<Vec<(Hash, AuctionData<AccountId, BlockNumber, Bid<AccountId>, Tier>)>> {
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+        self.client
+            .runtime_api()
+            .auctions_by_tier(at, level)
+            .map_err(|e| runtime_error("unable to query auctions_by_tier", e))
+    }
+
+    fn best_bid(&self, auction_id: Hash, at: Option<Block::Hash>) -> RpcResult<Option<Bid<AccountId>>> {
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+        self.client
+            .runtime_api()
+            .best_bid(at, auction_id)
+            .map_err(|e| runtime_error("unable to query best_bid", e))
+    }
+
+    fn live_auctions(&self, at: Option<Block::Hash>) -> RpcResult<Vec<Hash>> {
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+        self.client
+            .runtime_api()
+            .live_auctions(at)
+            .map_err(|e| runtime_error("unable to query live_auctions", e))
+    }
+}
@@ -0,0 +1,31 @@
+//! Runtime API for querying live auctions and best bids per tier, so front-ends can read the
+//! order book without decoding raw storage themselves.
+//!
+//! A node's runtime implements this via `impl_runtime_apis!`, backed by the pallet's
+//! `Auctions`/`AuctionsByTier` storage. The matching `jsonrpsee` server lives in [`crate::rpc`].
+
+use frame_support::inherent::Vec;
+
+use crate::{AuctionData, Bid, Tier};
+
+sp_api::decl_runtime_apis! {
+    /// Read-only queries over the auction order book.
+    pub trait AuctionApi<AccountId, BlockNumber, Hash>
+    where
+        AccountId: codec::Codec,
+        BlockNumber: codec::Codec,
+        Hash: codec::Codec,
+    {
+        /// The auctions in the given tier that are still `Alive`.
+        fn auctions_by_tier(
+            level: u32,
+        ) -> Vec<(Hash, AuctionData<AccountId, BlockNumber, Bid<AccountId>, Tier>)>;
+
+        /// The current highest bid in an auction's order book, if it has received one. A Dutch
+        /// auction's winning bid is not kept in the order book, so this is `None` for it.
+        fn best_bid(auction_id: Hash) -> Option<Bid<AccountId>>;
+
+        /// The ids of every auction that is still `Alive` and accepting bids.
+        fn live_auctions() -> Vec<Hash>;
+    }
+}
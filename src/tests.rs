@@ -0,0 +1,263 @@
+use crate::{
+    mock::*,
+    pallet::{AuctionsByTier, Auctions},
+    AuctionKind, AuctionStatus, Error, Event,
+};
+use frame_support::{assert_noop, assert_ok, traits::Hooks};
+
+fn only_auction_id() -> <Test as frame_system::Config>::Hash {
+    Auctions::<Test>::iter_keys().next().expect("an auction was created")
+}
+
+#[test]
+fn create_auction_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Auction::create_auction(
+            RuntimeOrigin::signed(SELLER),
+            50,
+            10,
+            10,
+            0,
+            AuctionKind::OrderBook,
+            0,
+            0,
+        ));
+
+        let auction_id = only_auction_id();
+        let auction = Auction::get_auction(auction_id).unwrap();
+        assert_eq!(auction.auction_status, AuctionStatus::Alive);
+        assert_eq!(auction.quantity, 50);
+        assert!(AuctionsByTier::<Test>::contains_key(auction.auction_category.level, auction_id));
+
+        System::assert_last_event(
+            Event::AuctionCreated { seller: SELLER, energy_quantity: 50, starting_price: 10 }.into(),
+        );
+    });
+}
+
+#[test]
+fn create_auction_rejects_zero_period() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Auction::create_auction(RuntimeOrigin::signed(SELLER), 50, 10, 0, 0, AuctionKind::OrderBook, 0, 0),
+            Error::<Test>::ZeroAuctionPeriod,
+        );
+    });
+}
+
+#[test]
+fn bid_on_auction_keeps_bids_sorted_descending() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Auction::create_auction(
+            RuntimeOrigin::signed(SELLER),
+            50,
+            10,
+            10,
+            0,
+            AuctionKind::OrderBook,
+            0,
+            0,
+        ));
+        let auction_id = only_auction_id();
+
+        assert_ok!(Auction::bid_on_auction(RuntimeOrigin::signed(BUYER_1), auction_id, 15, 10));
+        assert_ok!(Auction::bid_on_auction(RuntimeOrigin::signed(BUYER_2), auction_id, 20, 10));
+
+        let auction = Auction::get_auction(auction_id).unwrap();
+        assert_eq!(auction.bids[0].buyer_id, BUYER_2);
+        assert_eq!(auction.bids[1].buyer_id, BUYER_1);
+        assert_eq!(auction.highest_bid.buyer_id, BUYER_2);
+    });
+}
+
+#[test]
+fn bid_on_nonexistent_auction_fails() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Auction::bid_on_auction(RuntimeOrigin::signed(BUYER_1), Default::default(), 15, 10),
+            Error::<Test>::AuctionDoesNotExist,
+        );
+    });
+}
+
+#[test]
+fn dutch_auction_decays_and_matches_instantly() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Auction::create_auction(
+            RuntimeOrigin::signed(SELLER),
+            50,
+            10,
+            10,
+            0,
+            AuctionKind::Dutch,
+            100,
+            0,
+        ));
+        let auction_id = only_auction_id();
+
+        // Halfway through the decay (block 1 -> 6 of a 10-block period), the ask is ~50.
+        System::set_block_number(6);
+        assert_noop!(
+            Auction::bid_on_auction(RuntimeOrigin::signed(BUYER_1), auction_id, 40, 50),
+            Error::<Test>::BidTooLow,
+        );
+
+        assert_ok!(Auction::bid_on_auction(RuntimeOrigin::signed(BUYER_1), auction_id, 50, 50));
+        assert_eq!(Auction::get_auction(auction_id).unwrap().auction_status, AuctionStatus::Matched);
+    });
+}
+
+#[test]
+fn dutch_auction_rejects_partial_quantity() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Auction::create_auction(
+            RuntimeOrigin::signed(SELLER),
+            50,
+            10,
+            10,
+            0,
+            AuctionKind::Dutch,
+            100,
+            0,
+        ));
+        let auction_id = only_auction_id();
+
+        assert_noop!(
+            Auction::bid_on_auction(RuntimeOrigin::signed(BUYER_1), auction_id, 100, 1),
+            Error::<Test>::QuantityMustMatchLot,
+        );
+    });
+}
+
+#[test]
+fn bid_on_auction_rejects_second_bid_from_same_buyer() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Auction::create_auction(
+            RuntimeOrigin::signed(SELLER),
+            50,
+            10,
+            10,
+            0,
+            AuctionKind::OrderBook,
+            0,
+            0,
+        ));
+        let auction_id = only_auction_id();
+
+        assert_ok!(Auction::bid_on_auction(RuntimeOrigin::signed(BUYER_1), auction_id, 15, 10));
+        assert_noop!(
+            Auction::bid_on_auction(RuntimeOrigin::signed(BUYER_1), auction_id, 20, 5),
+            Error::<Test>::DuplicateBid,
+        );
+    });
+}
+
+#[test]
+fn on_initialize_clears_partial_fills_at_uniform_price() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Auction::create_auction(
+            RuntimeOrigin::signed(SELLER),
+            30,
+            5,
+            5,
+            0,
+            AuctionKind::OrderBook,
+            0,
+            0,
+        ));
+        let auction_id = only_auction_id();
+
+        // Demand (20 + 20 = 40) exceeds supply (30): only the higher bid fully clears, the
+        // lower bid partially clears, both at the lower bid's price (the uniform clearing price).
+        assert_ok!(Auction::bid_on_auction(RuntimeOrigin::signed(BUYER_1), auction_id, 20, 20));
+        assert_ok!(Auction::bid_on_auction(RuntimeOrigin::signed(BUYER_2), auction_id, 10, 20));
+
+        Auction::on_initialize(6);
+
+        // BUYER_1: fully filled at 20 for 20 units, but only pays the clearing price of 10.
+        assert_eq!(Balances::reserved_balance(BUYER_1), 0);
+        // BUYER_2: reserved 10 * 20 = 200, only 10 units clear at price 10 = 100 spent, 100 refunded.
+        assert_eq!(Balances::reserved_balance(BUYER_2), 0);
+    });
+}
+
+#[test]
+fn bid_on_auction_rejects_bids_outside_the_auctions_tier() {
+    new_test_ext().execute_with(|| {
+        // quantity 50 < the 100-unit threshold, so this auction is tier 1.
+        assert_ok!(Auction::create_auction(
+            RuntimeOrigin::signed(SELLER),
+            50,
+            10,
+            10,
+            0,
+            AuctionKind::OrderBook,
+            0,
+            0,
+        ));
+        let auction_id = only_auction_id();
+        assert_eq!(Auction::get_auction(auction_id).unwrap().auction_category.level, 1);
+
+        // A bid for 200 units is tier 2 and must not be allowed to match a tier-1 auction.
+        assert_noop!(
+            Auction::bid_on_auction(RuntimeOrigin::signed(BUYER_1), auction_id, 15, 200),
+            Error::<Test>::TierMismatch,
+        );
+
+        assert_ok!(Auction::bid_on_auction(RuntimeOrigin::signed(BUYER_1), auction_id, 15, 50));
+    });
+}
+
+#[test]
+fn on_initialize_settles_cleared_bid_and_pays_seller() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Auction::create_auction(
+            RuntimeOrigin::signed(SELLER),
+            50,
+            10,
+            5,
+            0,
+            AuctionKind::OrderBook,
+            0,
+            0,
+        ));
+        let auction_id = only_auction_id();
+        assert_ok!(Auction::bid_on_auction(RuntimeOrigin::signed(BUYER_1), auction_id, 15, 50));
+
+        let seller_free_before = Balances::free_balance(SELLER);
+        Auction::on_initialize(6);
+
+        assert_eq!(Balances::reserved_balance(BUYER_1), 0);
+        assert_eq!(Balances::free_balance(SELLER), seller_free_before + 15 * 50);
+        assert_eq!(Auction::get_auction(auction_id).unwrap().auction_status, AuctionStatus::Matched);
+    });
+}
+
+#[test]
+fn destroy_auction_refunds_bidders_and_is_seller_only() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Auction::create_auction(
+            RuntimeOrigin::signed(SELLER),
+            50,
+            10,
+            10,
+            0,
+            AuctionKind::OrderBook,
+            0,
+            0,
+        ));
+        let auction_id = only_auction_id();
+        assert_ok!(Auction::bid_on_auction(RuntimeOrigin::signed(BUYER_1), auction_id, 15, 10));
+
+        assert_noop!(
+            Auction::destroy_auction(RuntimeOrigin::signed(BUYER_1), auction_id),
+            Error::<Test>::UnAuthorizedCall,
+        );
+
+        let reserved_before = Balances::reserved_balance(BUYER_1);
+        assert_ok!(Auction::destroy_auction(RuntimeOrigin::signed(SELLER), auction_id));
+
+        assert_eq!(Balances::reserved_balance(BUYER_1), reserved_before - 150);
+        assert_eq!(Auction::get_auction(auction_id).unwrap().auction_status, AuctionStatus::Dead);
+    });
+}